@@ -2,10 +2,14 @@
 #![feature(generators)]
 #![feature(immovable_types)]
 #![feature(generator_trait)]
+#![feature(specialization)]
 
+use std::cell::Cell;
 use std::marker::Move;
+use std::mem;
 use std::ops::Generator;
 use std::ops::GeneratorState as State;
+use std::rc::Rc;
 
 pub struct NotReady(());
 
@@ -25,6 +29,35 @@ impl<'a, T: ?Move + Future> Future for &'a mut T {
     }
 }
 
+/// A future that is safe to poll after it has completed: once done, it
+/// reports "not ready forever" instead of panicking like a resumed generator would.
+pub enum Fuse<F: ?Move> {
+    Pending(F),
+    Done,
+}
+
+/// Wraps `future` so it can be polled again after completion.
+pub fn fuse<F: ?Move + Future>(future: F) -> Fuse<F> {
+    Fuse::Pending(future)
+}
+
+impl<F: ?Move + Future> Future for Fuse<F> {
+    type Return = F::Return;
+
+    fn poll(&mut self) -> Poll<Self::Return> {
+        match *self {
+            Fuse::Pending(ref mut f) => match f.poll() {
+                State::Complete(r) => {
+                    *self = Fuse::Done;
+                    State::Complete(r)
+                }
+                State::Yielded(y) => State::Yielded(y),
+            },
+            Fuse::Done => State::Yielded(NotReady(())),
+        }
+    }
+}
+
 pub struct AsFuture<T: ?Move>(T);
 
 impl<T: Generator<Yield = NotReady, Return = R> + ?Move, R> Future for AsFuture<T> {
@@ -59,54 +92,227 @@ macro_rules! await {
     })
 }
 
-pub fn map<A, F, U>(future: A, f: F) -> impl Future<Return = U> 
+/// Converts a value into a future: futures convert to themselves, and plain
+/// values convert to an already-`ready` future. Lets combinators like `map`,
+/// `select`, and `join` take either uniformly.
+pub trait IntoFuture: ?Move {
+    type Future: Future<Return = Self::Return>;
+    type Return;
+
+    fn into_future(self) -> Self::Future;
+}
+
+impl<T> IntoFuture for T {
+    default type Future = Ready<T>;
+    default type Return = T;
+
+    default fn into_future(self) -> Ready<T> {
+        ready(self)
+    }
+}
+
+impl<F: ?Move + Future> IntoFuture for F {
+    type Future = F;
+    type Return = F::Return;
+
+    fn into_future(self) -> F {
+        self
+    }
+}
+
+pub fn map<A, F, U>(future: A, f: F) -> impl Future<Return = U>
 where
-    A: Future,
+    A: IntoFuture,
     F: FnOnce(A::Return) -> U,
 {
     async! {
-        f(await!(future))
+        f(await!(future.into_future()))
     }
 }
 
-pub enum OneOf<A, B> {
+/// Builds a future from a closure that is polled directly, for bridging manually
+/// driven readiness sources (callbacks, etc.) into this crate's `Future` trait.
+pub fn poll_fn<F, R>(mut f: F) -> impl Future<Return = R>
+where
+    F: FnMut() -> Poll<R>,
+{
+    async! {
+        loop {
+            match f() {
+                State::Complete(r) => return r,
+                State::Yielded(y) => yield y,
+            }
+        }
+    }
+}
+
+pub struct Ready<R>(Option<R>);
+
+impl<R> Future for Ready<R> {
+    type Return = R;
+
+    fn poll(&mut self) -> Poll<Self::Return> {
+        State::Complete(self.0.take().expect("Ready polled after completion"))
+    }
+}
+
+/// A future that is immediately complete with `value`.
+pub fn ready<R>(value: R) -> Ready<R> {
+    Ready(Some(value))
+}
+
+/// A future that never completes.
+pub fn pending<R>() -> impl Future<Return = R> {
+    async! {
+        loop {
+            yield NotReady(())
+        }
+    }
+}
+
+/// Defers running `f` until the future is first polled, then completes with its result.
+pub fn lazy<F, R>(f: F) -> impl Future<Return = R>
+where
+    F: FnOnce() -> R,
+{
+    async! {
+        f()
+    }
+}
+
+/// Holds one of two (possibly differently-typed) futures, e.g. the untouched
+/// loser of a `select`, or either branch of an if/else.
+pub enum Either<A: ?Move, B: ?Move> {
     A(A),
     B(B),
 }
 
-impl<A: Future<Return = R>, B: Future<Return = R>, R> Future for OneOf<A, B> {
-    type Return = R;
+impl<A: ?Move + Future, B: ?Move + Future> Future for Either<A, B> {
+    type Return = Either<A::Return, B::Return>;
 
     fn poll(&mut self) -> Poll<Self::Return> {
         match *self {
-            OneOf::A(ref mut a) => a.poll(),
-            OneOf::B(ref mut b) => b.poll(),
+            Either::A(ref mut a) => match a.poll() {
+                State::Complete(r) => State::Complete(Either::A(r)),
+                State::Yielded(y) => State::Yielded(y),
+            },
+            Either::B(ref mut b) => match b.poll() {
+                State::Complete(r) => State::Complete(Either::B(r)),
+                State::Yielded(y) => State::Yielded(y),
+            },
+        }
+    }
+}
+
+impl<R: ?Move> Either<R, R> {
+    /// Flattens an `Either` of two identical types down to a single value.
+    pub fn into_inner(self) -> R {
+        match self {
+            Either::A(r) => r,
+            Either::B(r) => r,
         }
     }
 }
 
 /// Returns the result of the first future to finish and the uncompleted future
 /// This requires movable futures
-pub fn select<A, B, R>(mut a: A, mut b: B) -> impl Future<Return = (R, OneOf<A, B>)>
+pub fn select<A, B, R>(a: A, b: B) -> impl Future<Return = (R, Either<A::Future, B::Future>)>
 where
-    A: Future<Return = R>,
-    B: Future<Return = R>,
+    A: IntoFuture<Return = R>,
+    B: IntoFuture<Return = R>,
 {
+    let mut a = a.into_future();
+    let mut b = b.into_future();
     async! {
         loop {
             match a.poll() {
-                State::Complete(r) => return (r, OneOf::B(b)),
+                State::Complete(r) => return (r, Either::B(b)),
                 State::Yielded(_) => (),
             }
 
             match b.poll() {
-                State::Complete(r) => return (r, OneOf::A(a)),
+                State::Complete(r) => return (r, Either::A(a)),
                 State::Yielded(y) => yield y,
             }
         }
     }
 }
 
+/// Returns the result of the first future in `futures` to finish, its index, and the
+/// remaining futures with the completed one removed.
+/// This requires movable futures.
+///
+/// # Panics
+///
+/// Panics on the first poll if `futures` is empty.
+pub fn select_all<F, R>(mut futures: Vec<F>) -> impl Future<Return = (R, usize, Vec<F>)>
+where
+    F: Future<Return = R> + Move,
+{
+    async! {
+        loop {
+            let mut must_yield = None;
+            let mut completed = None;
+
+            for (i, f) in futures.iter_mut().enumerate() {
+                match f.poll() {
+                    State::Complete(r) => {
+                        completed = Some((i, r));
+                        break;
+                    }
+                    State::Yielded(y) => must_yield = Some(y),
+                }
+            }
+
+            if let Some((i, r)) = completed {
+                futures.remove(i);
+                return (r, i, futures);
+            }
+
+            yield must_yield.expect("select_all polled with no futures");
+        }
+    }
+}
+
+/// Returns the first future in `futures` to complete with `Ok`, along with the
+/// remaining futures, or the errors of every future if they all fail.
+/// This requires movable futures.
+pub fn select_ok<F, R, E>(mut futures: Vec<F>) -> impl Future<Return = Result<(R, Vec<F>), Vec<E>>>
+where
+    F: Future<Return = Result<R, E>> + Move,
+{
+    async! {
+        let mut errors = Vec::new();
+        loop {
+            let mut must_yield = None;
+            let mut i = 0;
+
+            while i < futures.len() {
+                match futures[i].poll() {
+                    State::Complete(Ok(r)) => {
+                        futures.remove(i);
+                        return Ok((r, futures));
+                    }
+                    State::Complete(Err(e)) => {
+                        errors.push(e);
+                        futures.remove(i);
+                    }
+                    State::Yielded(y) => {
+                        must_yield = Some(y);
+                        i += 1;
+                    }
+                }
+            }
+
+            if futures.is_empty() {
+                return Err(errors);
+            }
+
+            yield must_yield.expect("select_ok polled with no futures");
+        }
+    }
+}
+
 /// Returns the result of the first future to finish
 pub fn race<A: ?Move, B: ?Move, R>(mut a: A, mut b: B) -> impl Future<Return = R>
 where
@@ -118,12 +324,14 @@ where
     }
 }
 
-/// Waits for two futures to complete
-pub fn join<A: ?Move, B: ?Move, RA, RB>(mut a: A, mut b: B) -> impl Future<Return = (RA, RB)>
+/// Waits for two futures (or convertible-to-future values) to complete
+pub fn join<A: ?Move, B: ?Move>(a: A, b: B) -> impl Future<Return = (A::Return, B::Return)>
 where
-    A: Future<Return = RA>,
-    B: Future<Return = RB>,
+    A: IntoFuture,
+    B: IntoFuture,
 {
+    let mut a = a.into_future();
+    let mut b = b.into_future();
     async! {
         let mut ra = None;
         let mut rb = None;
@@ -151,4 +359,231 @@ where
             }
         }
     }
+}
+
+/// Tracks one slot of a `join!`/`join_all` group: once a future completes we
+/// stop polling it and hold onto its result until it's taken.
+///
+/// This is expansion plumbing for `join!` and `join_all`, not meant to be used directly.
+#[doc(hidden)]
+pub enum MaybeDone<F: ?Move + Future> {
+    Pending(F),
+    Done(F::Return),
+    Gone,
+}
+
+impl<F: ?Move + Future> MaybeDone<F> {
+    /// Polls the slot if it's still pending, recording `must_yield` if it isn't done yet.
+    #[doc(hidden)]
+    pub fn advance(&mut self, must_yield: &mut Option<NotReady>) {
+        let result = match *self {
+            MaybeDone::Pending(ref mut f) => match f.poll() {
+                State::Complete(r) => r,
+                State::Yielded(y) => {
+                    *must_yield = Some(y);
+                    return;
+                }
+            },
+            _ => return,
+        };
+        *self = MaybeDone::Done(result);
+    }
+
+    /// Takes the completed value out of the slot, leaving it `Gone`.
+    ///
+    /// Panics if the slot hasn't completed yet.
+    #[doc(hidden)]
+    pub fn take(&mut self) -> F::Return {
+        match mem::replace(self, MaybeDone::Gone) {
+            MaybeDone::Done(r) => r,
+            _ => panic!("MaybeDone taken before completion or taken twice"),
+        }
+    }
+}
+
+/// Drives a homogeneous collection of futures concurrently, resolving once
+/// every future in `futures` has completed, in the same order as the input.
+pub fn join_all<F>(futures: Vec<F>) -> impl Future<Return = Vec<F::Return>>
+where
+    F: Future + Move,
+{
+    async! {
+        let mut slots: Vec<_> = futures.into_iter().map(MaybeDone::Pending).collect();
+        loop {
+            let mut must_yield = None;
+
+            for slot in &mut slots {
+                slot.advance(&mut must_yield);
+            }
+
+            if let Some(y) = must_yield {
+                yield y
+            } else {
+                return slots.iter_mut().map(MaybeDone::take).collect();
+            }
+        }
+    }
+}
+
+/// Waits for any number of (possibly differently-typed) futures to all complete,
+/// resolving to a tuple of their results in argument order.
+///
+/// Expands by recursively binding each future to its own `MaybeDone` slot out of a
+/// fixed internal name pool, so the only practical limit is that pool's size.
+#[macro_export]
+macro_rules! join {
+    ($($f:expr),+ $(,)*) => {
+        $crate::__join_munch!(
+            []
+            [
+                __join_0 __join_1 __join_2 __join_3 __join_4 __join_5 __join_6 __join_7
+                __join_8 __join_9 __join_10 __join_11 __join_12 __join_13 __join_14 __join_15
+                __join_16 __join_17 __join_18 __join_19 __join_20 __join_21 __join_22 __join_23
+                __join_24 __join_25 __join_26 __join_27 __join_28 __join_29 __join_30 __join_31
+            ]
+            [$($f),+]
+        )
+    };
+}
+
+/// Implementation detail of `join!`: recursively binds one future per slot in the
+/// name pool, then drives every bound slot to completion in a single poll loop.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __join_munch {
+    ([$($slot:ident)*] [$($pool:ident)*] []) => {{
+        $crate::async! {
+            loop {
+                let mut must_yield = None;
+                $($slot.advance(&mut must_yield);)*
+                if let Some(y) = must_yield {
+                    yield y
+                } else {
+                    return ($($slot.take(),)*);
+                }
+            }
+        }
+    }};
+    ([$($slot:ident)*] [$pool_head:ident $($pool_rest:ident)*] [$f:expr]) => {{
+        let mut $pool_head = $crate::MaybeDone::Pending($f);
+        $crate::__join_munch!([$($slot)* $pool_head] [$($pool_rest)*] [])
+    }};
+    ([$($slot:ident)*] [$pool_head:ident $($pool_rest:ident)*] [$f:expr, $($rest:expr),+]) => {{
+        let mut $pool_head = $crate::MaybeDone::Pending($f);
+        $crate::__join_munch!([$($slot)* $pool_head] [$($pool_rest)*] [$($rest),+])
+    }};
+}
+
+/// Waits for two `Result`-returning futures to complete, bailing out with the first
+/// `Err` instead of waiting for both to finish.
+pub fn try_join<A: ?Move, B: ?Move, T1, T2, E>(
+    mut a: A,
+    mut b: B,
+) -> impl Future<Return = Result<(T1, T2), E>>
+where
+    A: Future<Return = Result<T1, E>>,
+    B: Future<Return = Result<T2, E>>,
+{
+    async! {
+        let mut ra = None;
+        let mut rb = None;
+        loop {
+            let mut must_yield = None;
+
+            if ra.is_none() {
+                match a.poll() {
+                    State::Complete(Ok(r)) => ra = Some(r),
+                    State::Complete(Err(e)) => return Err(e),
+                    State::Yielded(y) => must_yield = Some(y),
+                }
+            }
+
+            if rb.is_none() {
+                match b.poll() {
+                    State::Complete(Ok(r)) => rb = Some(r),
+                    State::Complete(Err(e)) => return Err(e),
+                    State::Yielded(y) => must_yield = Some(y),
+                }
+            }
+
+            if let Some(y) = must_yield {
+                yield y
+            } else {
+                return Ok((ra.unwrap(), rb.unwrap()));
+            }
+        }
+    }
+}
+
+/// Returns the first `Result`-returning future to complete: either its `Ok` value
+/// together with the uncompleted future, or its `Err`.
+/// This requires movable futures.
+pub fn try_select<A, B, T, E>(mut a: A, mut b: B) -> impl Future<Return = Result<(T, Either<A, B>), E>>
+where
+    A: Future<Return = Result<T, E>> + Move,
+    B: Future<Return = Result<T, E>> + Move,
+{
+    async! {
+        loop {
+            match a.poll() {
+                State::Complete(Ok(r)) => return Ok((r, Either::B(b))),
+                State::Complete(Err(e)) => return Err(e),
+                State::Yielded(_) => (),
+            }
+
+            match b.poll() {
+                State::Complete(Ok(r)) => return Ok((r, Either::A(a))),
+                State::Complete(Err(e)) => return Err(e),
+                State::Yielded(y) => yield y,
+            }
+        }
+    }
+}
+
+/// The error returned by an `Abortable` future that was stopped via its `AbortHandle`.
+pub struct Aborted;
+
+/// Lets an `Abortable` future be stopped cooperatively from outside its poll loop.
+pub struct AbortHandle {
+    aborted: Rc<Cell<bool>>,
+}
+
+impl AbortHandle {
+    /// Signals the paired `Abortable` to complete with `Err(Aborted)` on its next poll.
+    pub fn abort(&self) {
+        self.aborted.set(true);
+    }
+}
+
+pub struct Abortable<F: ?Move> {
+    future: F,
+    aborted: Rc<Cell<bool>>,
+}
+
+/// Wraps `future` so it can be stopped early via the returned `AbortHandle`.
+pub fn abortable<F: ?Move + Future>(future: F) -> (Abortable<F>, AbortHandle) {
+    let aborted = Rc::new(Cell::new(false));
+
+    (
+        Abortable {
+            future,
+            aborted: aborted.clone(),
+        },
+        AbortHandle { aborted },
+    )
+}
+
+impl<F: ?Move + Future> Future for Abortable<F> {
+    type Return = Result<F::Return, Aborted>;
+
+    fn poll(&mut self) -> Poll<Self::Return> {
+        if self.aborted.get() {
+            return State::Complete(Err(Aborted));
+        }
+
+        match self.future.poll() {
+            State::Complete(r) => State::Complete(Ok(r)),
+            State::Yielded(y) => State::Yielded(y),
+        }
+    }
 }
\ No newline at end of file